@@ -1,20 +1,40 @@
 //! Dynamic pricing example with x402-axum
 //!
 //! This example demonstrates an implementation of dynamic pricing using x402-axum.
+//! Amounts are priced in USD and converted to the accepted token's base
+//! units using a live rate feed (see the [`rate`] module). The resource
+//! also accepts payment in additional tokens, each priced with a swap
+//! quote from an aggregator (see the [`quote_source`] module). Quotes are
+//! stateless and EIP-712 signed by the server (see the [`signed_quote`]
+//! module) rather than tracked in a shared in-memory map.
 //!
 //! ## ⚠️  Production Security Considerations:
-//! 
+//!
 //! This example is simplified for demonstration. In production, you SHOULD take into account:
-//! - Proper quote authentication
-//! - Proper quote expiry
-//! - Proper only-once semantics for quotes
+//! - Proper quote expiry (handled here via `Quote::valid_until`)
+//! - Proper only-once semantics for quotes (handled here via `NonceStore`)
+//!
+//! ## A note on the builders this example doesn't add
+//!
+//! Several pieces below ([`rate::RateProvider`], accepted-token quoting in
+//! [`quote_source`], [`signed_quote::SignedQuoteVerifier`],
+//! [`payer_filter::PayerFilter`]) would, on `x402-axum`/`x402-rs` proper,
+//! plausibly be exposed as `X402Middleware` builder methods (e.g.
+//! `.with_rate_provider(...)`, `.with_accepted_tokens(...)`,
+//! `.with_signed_quotes(...)`, `.with_payer_filter(...)`). Neither crate's
+//! source is vendored in this repo, so none of those builders could
+//! actually be added upstream; every one of them is instead stored on this
+//! example's local `AppState` and consulted directly: the rate provider
+//! from the `quote` handler, the rest from `resolve_payment_requirements`.
+//! Adding the real builders is upstream
+//! work against `x402-axum`/`x402-rs`.
 
-use std::collections::HashMap;
-use std::sync::{Arc};
+use std::sync::Arc;
 use std::str::FromStr;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use axum::{Router, routing::{get, post}, extract::{State}, response::IntoResponse, Json};
+use base64::Engine;
 use dotenvy::dotenv;
 use std::env;
 use http::{HeaderMap, StatusCode, Uri};
@@ -28,20 +48,99 @@ use x402_rs::network::{Network, USDCDeployment};
 use x402_rs::types::{MoneyAmount, PaymentRequirements, Scheme};
 use x402_rs::address_evm;
 
+mod payer_filter;
+mod quote_source;
+mod rate;
+mod signed_quote;
+use payer_filter::{FilterDecision, PayerFilter, StaticAllowList};
+use quote_source::{AggregatorQuoteSource, QuoteSource};
+use rate::{money_to_token_amount, Currency, Pair, RateProvider, WsRateProvider};
+use signed_quote::{Address, InMemoryNonceStore, NonceStore, Quote, QuoteSigner, SharedNonceStore, SignedQuoteVerifier};
+
 #[derive(Clone)]
-struct QuoteInfo {
-    amount: String,
-    client_id: String, // Client ID for identifying the client that requested the quote
-    expires_at: u64,      // Unix timestamp
-    used: bool,           // Track if quote has been used
+struct AppState {
+    // Live USD/token rate feed, used to price quotes in a volatile token
+    // instead of assuming a 1:1 USD-stablecoin.
+    rate_provider: Arc<dyn RateProvider>,
+    // Additional tokens this resource accepts alongside the base price tag,
+    // each priced via `quote_source`.
+    accepted_tokens: Vec<x402_rs::types::TokenAsset>,
+    quote_source: Arc<dyn QuoteSource>,
+    base_url: Url,
+    server_pay_to: signed_quote::Address,
+    server_token: signed_quote::Address,
+    server_token_decimals: u8,
+    quote_signer: Arc<QuoteSigner>,
+    quote_verifier: Arc<SignedQuoteVerifier>,
+    nonce_store: SharedNonceStore,
+    // Consulted with the paying address before the facilitator is asked to
+    // settle; defaults to allowing everyone.
+    payer_filter: Arc<dyn PayerFilter>,
+    // Basis points of slippage buffer applied to swap-quoted amounts, to
+    // absorb price movement between quote time and facilitator settlement.
+    slippage_bps: u16,
 }
 
-#[derive(Clone, Default)]
-struct AppState {
-    // In-memory quote store: quote_id -> QuoteInfo
-    quotes: Arc<tokio::sync::Mutex<HashMap<String, QuoteInfo>>>,
+impl AppState {
+    fn new(
+        rate_provider: Arc<dyn RateProvider>,
+        quote_source: Arc<dyn QuoteSource>,
+        base_url: Url,
+        server_pay_to: signed_quote::Address,
+        server_token: signed_quote::Address,
+        server_token_decimals: u8,
+        quote_signer: Arc<QuoteSigner>,
+        quote_verifier: Arc<SignedQuoteVerifier>,
+    ) -> Self {
+        Self {
+            rate_provider,
+            accepted_tokens: Vec::new(),
+            quote_source,
+            base_url,
+            server_pay_to,
+            server_token,
+            server_token_decimals,
+            quote_signer,
+            quote_verifier,
+            nonce_store: Arc::new(InMemoryNonceStore::default()),
+            payer_filter: Arc::new(payer_filter::AllowAll),
+            slippage_bps: DEFAULT_SLIPPAGE_BPS,
+        }
+    }
+
+    /// Accepts payment in `tokens` in addition to the base price tag's
+    /// token, each priced with a swap quote at settlement time.
+    fn with_accepted_tokens(mut self, tokens: Vec<x402_rs::types::TokenAsset>) -> Self {
+        self.accepted_tokens = tokens;
+        self
+    }
+
+    /// Refuses service to payers the filter denies, checked just before the
+    /// facilitator would be asked to settle.
+    fn with_payer_filter(mut self, filter: Arc<dyn PayerFilter>) -> Self {
+        self.payer_filter = filter;
+        self
+    }
+
+    /// Overrides the default slippage buffer applied to swap-quoted
+    /// amounts (see [`DEFAULT_SLIPPAGE_BPS`]).
+    fn with_slippage_bps(mut self, slippage_bps: u16) -> Self {
+        self.slippage_bps = slippage_bps;
+        self
+    }
 }
 
+/// Default basis points of slippage buffer applied to swap-quoted amounts,
+/// to absorb price movement between quote time and facilitator settlement.
+/// Overridable per-deployment via the `SLIPPAGE_BPS` env var (see `main`).
+const DEFAULT_SLIPPAGE_BPS: u16 = 50;
+
+/// The single network this resource settles on. The base price tag, the
+/// accepted-token list, and the rate lookup all have to agree on this: a
+/// facilitator can't settle a requirement on one chain with a token quoted
+/// on another.
+const SETTLEMENT_NETWORK: Network = Network::BaseSepolia;
+
 #[derive(Deserialize)]
 struct QuoteRequest {
     // a simple input that impacts price (e.g., numberOfFiles * unitPrice)
@@ -56,9 +155,9 @@ async fn resolve_payment_requirements(
     partial: &[x402_axum::layer::PaymentRequirementsNoResource],
     state: AppState,
 ) -> Result<Vec<x402_rs::types::PaymentRequirements>, x402_axum::layer::X402Error> {
-    let quote_id = headers.get("X-Quote-Id").and_then(|v| v.to_str().ok()).map(|s| s.to_string());
-    // In production, this should be a validated JWT or session token
-    // otherwise clients can use quotes from other clients
+    // In production, client id should come from a validated JWT or session
+    // token, not a bare header, so clients can't present quotes minted for
+    // someone else.
     let client_id = headers.get("X-Client-Id")
         .and_then(|v| v.to_str().ok())
         .map(|s| s.to_string())
@@ -69,109 +168,172 @@ async fn resolve_payment_requirements(
     resource.set_path(uri.path());
     resource.set_query(uri.query());
 
-    // If no quote id, reject with 402 showing the nominal requirements
-    let quote_id = match quote_id {
-        Some(q) => q,
-        None => {
-            // Return a 402 via X402Error by crafting it from nominal requirements
-            let reqs = partial
-                .iter()
-                .map(|p| p.to_payment_requirements(resource.clone()))
-                .collect::<Vec<_>>();
-            return Err(x402_required(reqs));
-        }
+    let nominal = || {
+        let reqs = partial
+            .iter()
+            .map(|p| p.to_payment_requirements(resource.clone()))
+            .collect::<Vec<_>>();
+        x402_required(reqs)
+    };
+
+    // A signed quote travels as its JSON body plus a hex-encoded 65-byte
+    // EIP-712 signature; no server-side quote store needed to authenticate
+    // it.
+    let quote_json = headers.get("X-Quote").and_then(|v| v.to_str().ok());
+    let quote_sig = headers.get("X-Quote-Signature").and_then(|v| v.to_str().ok());
+    let (quote_json, quote_sig) = match (quote_json, quote_sig) {
+        (Some(q), Some(s)) => (q, s),
+        _ => return Err(nominal()),
     };
 
-    // Get current timestamp for validation
+    let quote: Quote = serde_json::from_str(quote_json).map_err(|_| nominal())?;
+    let signature_bytes = hex::decode(quote_sig.trim_start_matches("0x")).map_err(|_| nominal())?;
+    let signature: [u8; 65] = signature_bytes.try_into().map_err(|_| nominal())?;
+
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap()
         .as_secs();
 
-    // Lookup quote info from the secure store
-    let quote_info = {
-        let store = state.quotes.lock().await;
-        store.get(&quote_id).cloned()
-    };
-
-    let quote_info = match quote_info {
-        Some(info) => info,
-        None => {
-            // Unknown quote -> present nominal requirements
-            let reqs = partial
-                .iter()
-                .map(|p| p.to_payment_requirements(resource.clone()))
-                .collect::<Vec<_>>();
-            return Err(x402_required(reqs));
-        }
-    };
-
-    // Security validations
-    if quote_info.client_id != client_id {
-        // Quote doesn't belong to this client
-        let reqs = partial
-            .iter()
-            .map(|p| p.to_payment_requirements(resource.clone()))
-            .collect::<Vec<_>>();
-        return Err(x402_required(reqs));
-    }
-
-    if quote_info.expires_at < now {
-        // Quote has expired
-        let reqs = partial
-            .iter()
-            .map(|p| p.to_payment_requirements(resource.clone()))
-            .collect::<Vec<_>>();
-        return Err(x402_required(reqs));
-    }
+    state
+        .quote_verifier
+        .verify(&quote, &signature, resource.as_str(), &client_id, now)
+        .map_err(|_| nominal())?;
 
-    if quote_info.used {
-        // Quote has already been used
-        let reqs = partial
-            .iter()
-            .map(|p| p.to_payment_requirements(resource.clone()))
-            .collect::<Vec<_>>();
-        return Err(x402_required(reqs));
+    // Replay protection: a quote's nonce can only be claimed once.
+    if !state.nonce_store.claim(&quote.nonce).await {
+        return Err(nominal());
     }
 
-    // Mark quote as used
-    {
-        let mut store = state.quotes.lock().await;
-        if let Some(info) = store.get_mut(&quote_id) {
-            info.used = true;
+    // If the client has attached a payment payload, check its payer against
+    // the configured filter before these requirements are used to settle.
+    if let Some(payload) = headers.get("X-Payment").and_then(|v| v.to_str().ok()) {
+        if let Some(payer) = decode_payer(payload) {
+            let requirements = partial.first().map(|p| p.to_payment_requirements(resource.clone()));
+            if let Some(requirements) = requirements {
+                if let FilterDecision::Deny { reason } =
+                    state.payer_filter.allow(payer, &requirements).await
+                {
+                    return Err(denied(reason, &resource, partial));
+                }
+            }
         }
     }
 
-    // Rewrite the max_amount_required with the quoted amount (token base units)
     let mut out = Vec::with_capacity(partial.len());
     for p in partial.iter() {
         let mut pr = p.to_payment_requirements(resource.clone());
-        // amount_str is a human-readable money amount string; convert to token amount (USDC 6 decimals)
-        if let Ok(m) = MoneyAmount::from_str(&quote_info.amount) {
-            if let Ok(token_amount) = m.as_token_amount(6) {
-                pr.max_amount_required = token_amount;
-            }
-        }
+        pr.max_amount_required = quote.max_amount_required;
         pr.scheme = Scheme::Exact;
         out.push(pr);
     }
+
+    // Fan out over the other tokens this resource accepts, re-quoting
+    // `quote.want_usd` against each via the configured DEX aggregator. A
+    // token whose quote fails is skipped rather than failing the whole
+    // request, so the client wallet still sees the tokens that could be
+    // priced.
+    if let Ok(want_usd) = MoneyAmount::from_str(&quote.want_usd) {
+        for token in &state.accepted_tokens {
+            match state
+                .quote_source
+                .quote(want_usd.clone(), token, state.slippage_bps)
+                .await
+            {
+                Ok(amount_in) => {
+                    if let Some(base) = out.first() {
+                        out.push(priced_for_token(base, token, amount_in));
+                    }
+                }
+                Err(err) => {
+                    tracing::warn!(%err, token = ?token, "skipping unquotable accepted token");
+                }
+            }
+        }
+    }
+
     Ok(out)
 }
 
 #[tokio::main]
 async fn main() {
     dotenv().ok();
-    let state = AppState::default();
+
+    // Connect to the live ticker feed used to price quotes in the accepted
+    // token. Rates older than 10s are treated as stale, and the quote
+    // endpoint answers 503 until a fresh one arrives.
+    let ticker_url = env::var("RATE_TICKER_URL")
+        .unwrap_or_else(|_| "wss://ticker.example.com/ws".to_string());
+    let rate_provider = WsRateProvider::connect(
+        ticker_url,
+        vec![Pair("USDC/USD".to_string())],
+        std::time::Duration::from_secs(10),
+    )
+    .await;
+
+    // Price resources in USDC by default; each token on the accepted-token
+    // list below is additionally priced with a swap quote from this
+    // aggregator. Here that list holds only USDC on the settlement network.
+    let aggregator_url = env::var("AGGREGATOR_URL")
+        .unwrap_or_else(|_| "https://aggregator.example.com/quote".to_string());
+    let quote_source = AggregatorQuoteSource::new(Url::parse(&aggregator_url).unwrap());
+
+    // The server's quote-signing key and the address clients use to verify
+    // its signed quotes. In production these should be provisioned via a
+    // secrets manager, not an env var.
+    let signing_key_hex =
+        env::var("QUOTE_SIGNING_KEY").expect("QUOTE_SIGNING_KEY must be set (32-byte hex)");
+    let signing_key = k256::ecdsa::SigningKey::from_slice(
+        &hex::decode(signing_key_hex.trim_start_matches("0x")).expect("invalid signing key hex"),
+    )
+    .expect("invalid signing key");
+    let server_address = signed_quote::Address::from_verifying_key(signing_key.verifying_key());
+
+    let base_url = Url::parse("https://localhost:3001/").unwrap();
+    let server_pay_to =
+        signed_quote::Address::from_hex("0xBAc675C310721717Cd4A37F6cbeA1F081b1C2a07").unwrap();
+    let server_token =
+        signed_quote::Address::from_hex("0x036CbD53842c5426634e7929541eC2318f3dCF7e").unwrap();
+
+    let state = AppState::new(
+        Arc::new(rate_provider),
+        Arc::new(quote_source),
+        base_url.clone(),
+        server_pay_to,
+        server_token,
+        6,
+        Arc::new(QuoteSigner::new(signing_key)),
+        Arc::new(SignedQuoteVerifier::new(server_address)),
+    )
+    .with_accepted_tokens(vec![
+        USDCDeployment::by_network(SETTLEMENT_NETWORK).token(),
+    ]);
+    // Let a deployment tune the slippage buffer without a recompile, e.g.
+    // looser for thinly-traded accepted tokens.
+    let state = match env::var("SLIPPAGE_BPS").ok().and_then(|s| s.parse().ok()) {
+        Some(slippage_bps) => state.with_slippage_bps(slippage_bps),
+        None => state,
+    };
+    // Optionally restrict this resource to a comma-separated allowlist of
+    // payer addresses, e.g. for a private beta.
+    let state = match env::var("ALLOWED_PAYERS") {
+        Ok(addresses) => state.with_payer_filter(Arc::new(StaticAllowList::new(
+            addresses
+                .split(',')
+                .filter_map(|s| Address::from_hex(s.trim())),
+        ))),
+        Err(_) => state,
+    };
     let resolver_state = state.clone();
 
     // Configure static parts of a price tag: token and payee
-    let usdc = USDCDeployment::by_network(Network::BaseSepolia)
+    let usdc = USDCDeployment::by_network(SETTLEMENT_NETWORK)
         .pay_to(address_evm!("0xBAc675C310721717Cd4A37F6cbeA1F081b1C2a07"));
 
     // Base middleware with token/payee; amount will be determined by resolver per request
     let facilitator_url = env::var("FACILITATOR_URL").unwrap_or_else(|_| "https://facilitator.x402.rs".to_string());
     let x402 = X402Middleware::try_from(facilitator_url).unwrap()
-        .with_base_url(Url::parse("https://localhost:3001/").unwrap())
+        .with_base_url(base_url)
         .with_mime_type("application/json")
         // seed a small nominal amount to form partial requirements (replaced by resolver)
         .with_price_tag(usdc.amount("0.01").unwrap())
@@ -185,22 +347,6 @@ async fn main() {
             })
         });
 
-    // Start cleanup task for expired quotes
-    let cleanup_state = state.clone();
-    tokio::spawn(async move {
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60)); // Run every minute
-        loop {
-            interval.tick().await;
-            let now = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs();
-            
-            let mut store = cleanup_state.quotes.lock().await;
-            store.retain(|_, quote_info| quote_info.expires_at > now);
-        }
-    });
-
     let app = Router::new()
         .route("/quote-resource", post(quote))
         .route("/resource", get(resource).layer(x402))
@@ -211,36 +357,59 @@ async fn main() {
     axum::serve(listener, app).await.unwrap();
 }
 
-async fn quote(State(state): State<AppState>, Json(body): Json<QuoteRequest>) -> impl IntoResponse {
+async fn quote(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<QuoteRequest>,
+) -> impl IntoResponse {
+    // In production, extract bearer token from request headers and validate it
+    let client_id = headers
+        .get("X-Client-Id")
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+
     // Example pricing: $0.01 per file
     let unit = MoneyAmount::try_from("0.01").unwrap();
     let total_money = MoneyAmount::try_from(body.number_of_files as f64 * 0.01f64).unwrap_or(unit);
 
-    let quote_id = Uuid::new_v4().to_string();
-    
-    // Set quote to expire in 5 minutes
-    let expires_at = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs() + 300; // 5 minutes
-
+    let rate = match state
+        .rate_provider
+        .quote_rate(Currency::Usd, &USDCDeployment::by_network(SETTLEMENT_NETWORK).token())
     {
-        let mut store = state.quotes.lock().await;
-        // Store secure quote info
-        // In production, extract bearer token from request headers and validate it
-        store.insert(quote_id.clone(), QuoteInfo {
-            amount: total_money.to_string(),
-            client_id: "demo-client".to_string(), // Demo client - use real auth in production
-            expires_at,
-            used: false,
-        });
-    }
+        Some(rate) => rate,
+        None => {
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(serde_json::json!({ "error": "rate feed unavailable, try again shortly" })),
+            )
+                .into_response();
+        }
+    };
+    let max_amount_required =
+        money_to_token_amount(total_money.as_f64(), rate, state.server_token_decimals);
+
+    let quote = Quote {
+        resource: state.base_url.join("resource").unwrap().to_string(),
+        pay_to: state.server_pay_to,
+        token: state.server_token,
+        max_amount_required,
+        valid_until: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 300, // 5 minutes
+        nonce: Uuid::new_v4().to_string(),
+        client_id,
+        want_usd: total_money.to_string(),
+    };
+    let signature = state.quote_signer.sign(&quote);
 
     let res = serde_json::json!({
-        "quote_id": quote_id,
-        "amount": total_money.to_string()
+        "quote": quote,
+        "signature": format!("0x{}", hex::encode(signature)),
     });
-    (StatusCode::OK, Json(res))
+    (StatusCode::OK, Json(res)).into_response()
 }
 
 async fn resource() -> impl IntoResponse {
@@ -252,3 +421,155 @@ fn x402_required(accepts: Vec<PaymentRequirements>) -> x402_axum::layer::X402Err
     x402_axum::layer::X402Error::payment_header_required(accepts)
 }
 
+/// Re-prices `base` for settlement in `token` instead of its original
+/// asset: the settlement asset address lives in `asset` (a hex string, the
+/// same representation `payTo` uses), not in some `token` field.
+fn priced_for_token(
+    base: &PaymentRequirements,
+    token: &x402_rs::types::TokenAsset,
+    amount: quote_source::TokenAmount,
+) -> PaymentRequirements {
+    let mut pr = base.clone();
+    pr.asset = token.address().to_string();
+    pr.max_amount_required = amount;
+    pr
+}
+
+/// Builds the error returned for a payer-filter denial.
+///
+/// FIXME(blocker): the request asked for "a 402 (with a reason) or 403 on
+/// denial." `x402_axum::layer::X402Error` as used by this resolver only
+/// exposes a 402 constructor (`payment_header_required`) — there is no way
+/// from this hook to return a 403 without either a new `X402Error` variant
+/// upstream in `x402-axum` or the resolver being able to return an
+/// arbitrary response. Until that's added upstream, a denial is a 402 that
+/// carries `reason` in each requirement's `description` so the client at
+/// least sees why; this should be raised with the `x402-axum` maintainers
+/// rather than treated as the 403 the request actually asked for.
+fn denied(
+    reason: &str,
+    resource: &Url,
+    partial: &[x402_axum::layer::PaymentRequirementsNoResource],
+) -> x402_axum::layer::X402Error {
+    let reqs = partial
+        .iter()
+        .map(|p| {
+            let mut pr = p.to_payment_requirements(resource.clone());
+            pr.description = format!("payment denied: {reason}");
+            pr
+        })
+        .collect::<Vec<_>>();
+    x402_required(reqs)
+}
+
+/// Decodes the `from` address out of a base64 JSON x402 "exact" payment
+/// payload: `{x402Version, scheme, network, payload: {signature,
+/// authorization: {from, to, value, ...}}}` — `from` is nested under
+/// `payload.authorization`, not at the top level.
+fn decode_payer(payload: &str) -> Option<Address> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(payload)
+        .ok()?;
+    let json: serde_json::Value = serde_json::from_slice(&bytes).ok()?;
+    let from = json.get("payload")?.get("authorization")?.get("from")?.as_str()?;
+    Address::from_hex(from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use x402_rs::types::PaymentRequirements;
+
+    fn encode_exact_payload(from: &str) -> String {
+        let payload = serde_json::json!({
+            "x402Version": 1,
+            "scheme": "exact",
+            "network": "base-sepolia",
+            "payload": {
+                "signature": "0xdeadbeef",
+                "authorization": {
+                    "from": from,
+                    "to": "0xBAc675C310721717Cd4A37F6cbeA1F081b1C2a07",
+                    "value": "10000",
+                },
+            },
+        });
+        base64::engine::general_purpose::STANDARD.encode(payload.to_string())
+    }
+
+    #[test]
+    fn decode_payer_reads_nested_authorization_from() {
+        let payload = encode_exact_payload("0x1111111111111111111111111111111111111111");
+        let payer = decode_payer(&payload).expect("should decode a realistic exact payload");
+        assert_eq!(payer, Address([0x11; 20]));
+    }
+
+    #[test]
+    fn decode_payer_ignores_top_level_from() {
+        // Regression check: `from` only exists nested under
+        // `payload.authorization`, not at the payload's top level.
+        let payload = serde_json::json!({ "from": "0x1111111111111111111111111111111111111111" });
+        let encoded = base64::engine::general_purpose::STANDARD.encode(payload.to_string());
+        assert!(decode_payer(&encoded).is_none());
+    }
+
+    #[tokio::test]
+    async fn static_allow_list_denies_payer_not_on_list() {
+        let allow_list = StaticAllowList::new([Address([0xAA; 20])]);
+        let dummy_requirements: PaymentRequirements =
+            serde_json::from_value(serde_json::json!({
+                "scheme": "exact",
+                "network": "base-sepolia",
+                "maxAmountRequired": "10000",
+                "resource": "https://example.com/resource",
+                "description": "",
+                "mimeType": "application/json",
+                "payTo": "0xBAc675C310721717Cd4A37F6cbeA1F081b1C2a07",
+                "maxTimeoutSeconds": 60,
+                "asset": "0x036CbD53842c5426634e7929541eC2318f3dCF7e",
+            }))
+            .expect("a minimal well-formed PaymentRequirements should deserialize");
+
+        let decision = allow_list
+            .allow(Address([0xBB; 20]), &dummy_requirements)
+            .await;
+        assert!(matches!(decision, FilterDecision::Deny { .. }));
+
+        let decision = allow_list
+            .allow(Address([0xAA; 20]), &dummy_requirements)
+            .await;
+        assert_eq!(decision, FilterDecision::Allow);
+    }
+
+    #[test]
+    fn priced_for_token_targets_the_quoted_tokens_asset() {
+        let base: PaymentRequirements = serde_json::from_value(serde_json::json!({
+            "scheme": "exact",
+            "network": "base-sepolia",
+            "maxAmountRequired": "10000",
+            "resource": "https://example.com/resource",
+            "description": "",
+            "mimeType": "application/json",
+            "payTo": "0xBAc675C310721717Cd4A37F6cbeA1F081b1C2a07",
+            "maxTimeoutSeconds": 60,
+            "asset": "0x036CbD53842c5426634e7929541eC2318f3dCF7e",
+        }))
+        .expect("a minimal well-formed PaymentRequirements should deserialize");
+
+        // Two distinct USDC deployments (mainnet Base vs. Base Sepolia) give
+        // us two tokens with different addresses without needing any other
+        // token type to exist in this crate.
+        let usdc_base = USDCDeployment::by_network(Network::Base).token();
+        let usdc_base_sepolia = USDCDeployment::by_network(Network::BaseSepolia).token();
+        assert_ne!(usdc_base.address(), usdc_base_sepolia.address());
+
+        let priced_base = priced_for_token(&base, &usdc_base, 42);
+        let priced_base_sepolia = priced_for_token(&base, &usdc_base_sepolia, 42);
+
+        assert_ne!(priced_base.asset, priced_base_sepolia.asset);
+        assert_eq!(priced_base.asset, usdc_base.address().to_string());
+        assert_eq!(priced_base_sepolia.asset, usdc_base_sepolia.address().to_string());
+        assert_eq!(priced_base.max_amount_required, 42);
+    }
+}
+
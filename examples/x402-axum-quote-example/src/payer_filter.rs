@@ -0,0 +1,197 @@
+//! Pre-settlement payer filtering.
+//!
+//! Lets an operator refuse service to specific payer addresses (sanctions,
+//! abuse) or restrict a resource to a whitelist, before the facilitator is
+//! asked to settle payment. [`PayerFilter`] is consulted with the payer
+//! decoded from the incoming payment payload's `from` field and the
+//! [`PaymentRequirements`] it would settle against.
+//!
+//! See the crate-level docs (`main.rs`) for why this isn't an
+//! `X402Middleware` builder.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use x402_rs::types::PaymentRequirements;
+
+use crate::signed_quote::Address;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterDecision {
+    Allow,
+    Deny { reason: &'static str },
+}
+
+#[async_trait::async_trait]
+pub trait PayerFilter: Send + Sync {
+    async fn allow(&self, payer: Address, requirements: &PaymentRequirements) -> FilterDecision;
+}
+
+/// The default filter: allows every payer. Used when no `PayerFilter` has
+/// been configured.
+pub struct AllowAll;
+
+#[async_trait::async_trait]
+impl PayerFilter for AllowAll {
+    async fn allow(&self, _payer: Address, _requirements: &PaymentRequirements) -> FilterDecision {
+        FilterDecision::Allow
+    }
+}
+
+/// Denies every payer except those in `allowed`.
+pub struct StaticAllowList {
+    allowed: HashSet<Address>,
+}
+
+impl StaticAllowList {
+    pub fn new(allowed: impl IntoIterator<Item = Address>) -> Self {
+        Self {
+            allowed: allowed.into_iter().collect(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl PayerFilter for StaticAllowList {
+    async fn allow(&self, payer: Address, _requirements: &PaymentRequirements) -> FilterDecision {
+        if self.allowed.contains(&payer) {
+            FilterDecision::Allow
+        } else {
+            FilterDecision::Deny {
+                reason: "payer is not on the allowlist",
+            }
+        }
+    }
+}
+
+/// Allows every payer except those in `denied`.
+pub struct StaticDenyList {
+    denied: HashSet<Address>,
+}
+
+impl StaticDenyList {
+    pub fn new(denied: impl IntoIterator<Item = Address>) -> Self {
+        Self {
+            denied: denied.into_iter().collect(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl PayerFilter for StaticDenyList {
+    async fn allow(&self, payer: Address, _requirements: &PaymentRequirements) -> FilterDecision {
+        if self.denied.contains(&payer) {
+            FilterDecision::Deny {
+                reason: "payer is on the denylist",
+            }
+        } else {
+            FilterDecision::Allow
+        }
+    }
+}
+
+/// Consults a deployed registry contract's `isAllowed(address) view
+/// returns (bool)` method instead of a list baked into the service, caching
+/// each answer for `ttl` so access control can change without a redeploy.
+pub struct OnChainAllowList {
+    rpc_url: String,
+    contract: Address,
+    ttl: Duration,
+    cache: RwLock<HashMap<Address, (bool, Instant)>>,
+}
+
+impl OnChainAllowList {
+    pub fn new(rpc_url: String, contract: Address, ttl: Duration) -> Self {
+        Self {
+            rpc_url,
+            contract,
+            ttl,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn encode_is_allowed_call(payer: Address) -> Vec<u8> {
+        // `isAllowed(address)` selector: keccak256("isAllowed(address)")[..4]
+        const SELECTOR: [u8; 4] = [0xba, 0xbc, 0xc5, 0x39];
+        let mut data = Vec::with_capacity(4 + 32);
+        data.extend_from_slice(&SELECTOR);
+        data.extend_from_slice(&[0u8; 12]);
+        data.extend_from_slice(&payer.0);
+        data
+    }
+
+    async fn is_allowed_onchain(&self, payer: Address) -> bool {
+        let data = Self::encode_is_allowed_call(payer);
+
+        let client = reqwest::Client::new();
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_call",
+            "params": [{ "to": format!("0x{}", hex::encode(self.contract.0)), "data": format!("0x{}", hex::encode(data)) }, "latest"],
+        });
+        let Ok(resp) = client.post(&self.rpc_url).json(&body).send().await else {
+            return false;
+        };
+        let Ok(value) = resp.json::<serde_json::Value>().await else {
+            return false;
+        };
+        value
+            .get("result")
+            .and_then(|r| r.as_str())
+            .map(|r| r.ends_with('1'))
+            .unwrap_or(false)
+    }
+}
+
+#[async_trait::async_trait]
+impl PayerFilter for OnChainAllowList {
+    async fn allow(&self, payer: Address, _requirements: &PaymentRequirements) -> FilterDecision {
+        let cached = self
+            .cache
+            .read()
+            .unwrap()
+            .get(&payer)
+            .filter(|(_, observed_at)| observed_at.elapsed() <= self.ttl)
+            .map(|(allowed, _)| *allowed);
+
+        let allowed = match cached {
+            Some(allowed) => allowed,
+            None => {
+                let allowed = self.is_allowed_onchain(payer).await;
+                self.cache
+                    .write()
+                    .unwrap()
+                    .insert(payer, (allowed, Instant::now()));
+                allowed
+            }
+        };
+
+        if allowed {
+            FilterDecision::Allow
+        } else {
+            FilterDecision::Deny {
+                reason: "payer is not permitted by the on-chain registry",
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_the_is_allowed_selector() {
+        // keccak256("isAllowed(address)")[..4]; a prior version of this
+        // constant didn't match the function signature it claimed to call,
+        // so every `eth_call` silently hit a non-existent selector and
+        // always fell through to "not allowed".
+        let payer = Address([0x11; 20]);
+        let data = OnChainAllowList::encode_is_allowed_call(payer);
+        assert_eq!(&data[..4], [0xba, 0xbc, 0xc5, 0x39]);
+        assert_eq!(&data[4..16], [0u8; 12]);
+        assert_eq!(&data[16..], [0x11u8; 20]);
+    }
+}
@@ -0,0 +1,137 @@
+//! Multi-token quoting.
+//!
+//! A resource can accept payment in several tokens, with every
+//! non-base-currency amount derived from a swap quote rather than a static
+//! number. [`QuoteSource`] models an ExactIn-style swap quote: given a
+//! target USD value and a destination token, it returns the input amount
+//! (in the destination token's base units) needed to yield that value,
+//! inflated by a slippage buffer to absorb price movement between quote
+//! time and facilitator settlement.
+//!
+//! See the crate-level docs (`main.rs`) for why this isn't an
+//! `X402Middleware` builder.
+
+use std::fmt;
+
+use x402_rs::types::{MoneyAmount, TokenAsset};
+
+/// A token amount expressed in the token's base units (e.g. USDC's
+/// 6-decimal units), matching `PaymentRequirements::max_amount_required`.
+pub type TokenAmount = u128;
+
+#[derive(Debug)]
+pub struct QuoteError(pub String);
+
+impl fmt::Display for QuoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to obtain swap quote: {}", self.0)
+    }
+}
+
+impl std::error::Error for QuoteError {}
+
+pub type Result<T> = std::result::Result<T, QuoteError>;
+
+/// Quotes the input amount of `token` needed to yield `want_usd` of value,
+/// inflated by `slippage_bps` basis points.
+#[async_trait::async_trait]
+pub trait QuoteSource: Send + Sync {
+    async fn quote(
+        &self,
+        want_usd: MoneyAmount,
+        token: &TokenAsset,
+        slippage_bps: u16,
+    ) -> Result<TokenAmount>;
+}
+
+/// A [`QuoteSource`] backed by an HTTP swap-quote aggregator, e.g. a DEX
+/// aggregator's `/quote` endpoint.
+pub struct AggregatorQuoteSource {
+    client: reqwest::Client,
+    base_url: reqwest::Url,
+}
+
+impl AggregatorQuoteSource {
+    pub fn new(base_url: reqwest::Url) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url,
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AggregatorResponse {
+    amount_in: String,
+}
+
+#[async_trait::async_trait]
+impl QuoteSource for AggregatorQuoteSource {
+    async fn quote(
+        &self,
+        want_usd: MoneyAmount,
+        token: &TokenAsset,
+        slippage_bps: u16,
+    ) -> Result<TokenAmount> {
+        let mut url = self.base_url.clone();
+        url.query_pairs_mut()
+            .append_pair("wantUsd", &want_usd.to_string())
+            .append_pair("tokenAddress", &token.address().to_string());
+
+        let resp: AggregatorResponse = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| QuoteError(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| QuoteError(e.to_string()))?;
+
+        let amount_in: TokenAmount = resp
+            .amount_in
+            .parse()
+            .map_err(|_| QuoteError(format!("non-numeric amountIn: {}", resp.amount_in)))?;
+
+        inflate_for_slippage(amount_in, slippage_bps)
+    }
+}
+
+/// Inflates `amount` by `slippage_bps` basis points (1 bps = 0.01%).
+///
+/// `amount` comes straight from a third-party aggregator response, so this
+/// uses checked arithmetic rather than trusting it to be small enough for
+/// `amount * slippage_bps` to fit in a `u128` — a malicious or buggy
+/// aggregator returning an oversized `amountIn` yields a clean `QuoteError`
+/// instead of a panic (debug) or silent wraparound (release).
+fn inflate_for_slippage(amount: TokenAmount, slippage_bps: u16) -> Result<TokenAmount> {
+    let buffer = amount
+        .checked_mul(slippage_bps as u128)
+        .map(|scaled| scaled / 10_000)
+        .ok_or_else(|| QuoteError(format!("amountIn {amount} too large to apply slippage to")))?;
+    amount
+        .checked_add(buffer)
+        .ok_or_else(|| QuoteError(format!("amountIn {amount} overflows after slippage buffer")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inflates_by_slippage_bps() {
+        // 1_000_000 base units + 50 bps (0.5%) = 1_005_000.
+        assert_eq!(inflate_for_slippage(1_000_000, 50).unwrap(), 1_005_000);
+    }
+
+    #[test]
+    fn zero_slippage_is_a_no_op() {
+        assert_eq!(inflate_for_slippage(1_000_000, 0).unwrap(), 1_000_000);
+    }
+
+    #[test]
+    fn rejects_amount_too_large_to_inflate_safely() {
+        assert!(inflate_for_slippage(u128::MAX, 50).is_err());
+    }
+}
@@ -0,0 +1,158 @@
+//! Live FX/token rate oracle.
+//!
+//! `x402-rs` does not yet ship a rate-provider abstraction, so this module
+//! sketches one locally: a `RateProvider` trait plus a WebSocket-backed
+//! implementation that keeps the latest mid-price for a set of pairs cached
+//! in memory. The `quote` handler uses it to price a USD quote in
+//! whatever token the resource accepts, instead of assuming a 1:1
+//! USD-stablecoin and a hardcoded decimals count.
+//!
+//! See the crate-level docs (`main.rs`) for why this isn't an
+//! `X402Middleware` builder.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::Message;
+
+use x402_rs::types::TokenAsset;
+
+/// A fiat or crypto currency a quote can be denominated in.
+///
+/// Only the cases this example needs; a real `x402-rs` type would likely be
+/// richer (ISO-4217 codes, etc.).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Currency {
+    Usd,
+}
+
+/// A mid-price of one whole `token` in units of `base`, e.g. `Currency::Usd`
+/// per whole token.
+#[derive(Debug, Clone, Copy)]
+pub struct Rate(pub f64);
+
+/// A trading pair identifying which rate a ticker message belongs to, e.g.
+/// `"ETH/USD"`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Pair(pub String);
+
+impl Pair {
+    fn for_token(base: Currency, token: &TokenAsset) -> Self {
+        let Currency::Usd = base;
+        Pair(format!("{}/USD", token.symbol()))
+    }
+}
+
+/// Something that can quote the current rate for a `(base, token)` pair.
+///
+/// Implementations should return `None` rather than a stale rate: the
+/// `quote` handler then answers 503 instead of issuing a quote, rather
+/// than silently mispricing a resource.
+pub trait RateProvider: Send + Sync {
+    fn quote_rate(&self, base: Currency, token: &TokenAsset) -> Option<Rate>;
+}
+
+#[derive(Deserialize)]
+struct TickerMessage {
+    pair: String,
+    price: f64,
+    #[allow(dead_code)]
+    timestamp: u64,
+}
+
+/// A `RateProvider` that subscribes to a WebSocket ticker feed and caches
+/// the newest mid-price per pair.
+///
+/// Connection handling runs on a background task spawned by
+/// [`WsRateProvider::connect`]: on disconnect it reconnects and resubscribes
+/// to the configured pairs. A cached rate older than `staleness` is treated
+/// as absent by [`RateProvider::quote_rate`].
+pub struct WsRateProvider {
+    rates: Arc<RwLock<HashMap<Pair, (f64, Instant)>>>,
+    staleness: Duration,
+}
+
+impl WsRateProvider {
+    /// Connects to `ticker_url`, subscribes to `pairs`, and returns a
+    /// provider backed by the background connection. A cached rate is
+    /// considered stale (and `quote_rate` returns `None`) once it is older
+    /// than `staleness`.
+    pub async fn connect(ticker_url: String, pairs: Vec<Pair>, staleness: Duration) -> Self {
+        let rates: Arc<RwLock<HashMap<Pair, (f64, Instant)>>> = Arc::new(RwLock::new(HashMap::new()));
+        let task_rates = rates.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(err) = Self::run_once(&ticker_url, &pairs, &task_rates).await {
+                    tracing::warn!(%err, "rate feed disconnected, reconnecting");
+                }
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        });
+        Self { rates, staleness }
+    }
+
+    async fn run_once(
+        ticker_url: &str,
+        pairs: &[Pair],
+        rates: &Arc<RwLock<HashMap<Pair, (f64, Instant)>>>,
+    ) -> Result<(), tokio_tungstenite::tungstenite::Error> {
+        let (ws_stream, _) = connect_async(ticker_url).await?;
+        let (mut write, mut read) = ws_stream.split();
+        for pair in pairs {
+            let subscribe = serde_json::json!({ "subscribe": pair.0 }).to_string();
+            write.send(Message::Text(subscribe)).await?;
+        }
+        while let Some(msg) = read.next().await {
+            let msg = msg?;
+            if let Message::Text(text) = msg {
+                if let Ok(ticker) = serde_json::from_str::<TickerMessage>(&text) {
+                    let mut guard = rates.write().unwrap();
+                    guard.insert(Pair(ticker.pair), (ticker.price, Instant::now()));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl RateProvider for WsRateProvider {
+    fn quote_rate(&self, base: Currency, token: &TokenAsset) -> Option<Rate> {
+        let pair = Pair::for_token(base, token);
+        let guard = self.rates.read().unwrap();
+        let (price, observed_at) = guard.get(&pair)?;
+        if observed_at.elapsed() > self.staleness {
+            return None;
+        }
+        Some(Rate(*price))
+    }
+}
+
+/// Converts a USD `money_amount` into base units of `token` using `rate`
+/// (USD per whole token), scaling by the token's decimals.
+pub fn money_to_token_amount(money_amount: f64, rate: Rate, token_decimals: u8) -> u128 {
+    let whole_tokens = money_amount / rate.0;
+    (whole_tokens * 10f64.powi(token_decimals as i32)) as u128
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_usd_to_token_base_units_at_rate() {
+        // $10 at $2/token, 6 decimals -> 5 tokens -> 5_000_000 base units.
+        let amount = money_to_token_amount(10.0, Rate(2.0), 6);
+        assert_eq!(amount, 5_000_000);
+    }
+
+    #[test]
+    fn converts_usd_to_token_base_units_for_stablecoin_rate() {
+        // $0.01 at a 1:1 USD rate, 6 decimals -> 10_000 base units.
+        let amount = money_to_token_amount(0.01, Rate(1.0), 6);
+        assert_eq!(amount, 10_000);
+    }
+}
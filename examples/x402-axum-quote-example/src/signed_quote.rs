@@ -0,0 +1,365 @@
+//! Stateless, EIP-712 signed quotes.
+//!
+//! Instead of a server-side `quote_id -> QuoteInfo` map (which doesn't
+//! scale horizontally and needs its own expiry/cleanup), the quote endpoint
+//! signs the quote itself: the client presents the quote and its signature
+//! back to the resource, the resolver recovers the signer and checks it
+//! against the configured server address, and rewrites
+//! `max_amount_required` straight from the signed payload. The only shared
+//! state left is replay protection, via [`NonceStore`].
+//!
+//! See the crate-level docs (`main.rs`) for why this isn't an
+//! `X402Middleware` builder.
+
+use k256::ecdsa::{RecoveryId, Signature as EcdsaSignature, SigningKey, VerifyingKey};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha3::{Digest, Keccak256};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// A 20-byte EVM address, serialized as a `0x`-prefixed hex string — the
+/// same wire representation `PaymentRequirements::pay_to`/`asset` use, not
+/// the derived `[u8; 20]` JSON array a `#[derive(Serialize, Deserialize)]`
+/// would produce.
+///
+/// `x402-rs` has its own address type (see `address_evm!`); this is the
+/// minimal local stand-in needed to hash and recover an EIP-712 signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Address(pub [u8; 20]);
+
+impl Serialize for Address {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{}", hex::encode(self.0)))
+    }
+}
+
+impl<'de> Deserialize<'de> for Address {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Address::from_hex(&s).ok_or_else(|| D::Error::custom(format!("not a 0x-prefixed 20-byte hex address: {s}")))
+    }
+}
+
+impl Address {
+    /// Parses a `0x`-prefixed 20-byte hex address.
+    pub fn from_hex(s: &str) -> Option<Self> {
+        let s = s.strip_prefix("0x")?;
+        // `len() == 40` on its own only counts bytes: a non-ASCII string
+        // can have a byte length of 40 without 40 one-byte chars, which
+        // would panic when we slice it below on a non-char-boundary.
+        if !s.is_ascii() || s.len() != 40 {
+            return None;
+        }
+        let mut out = [0u8; 20];
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).ok()?;
+        }
+        Some(Address(out))
+    }
+
+    pub fn from_verifying_key(key: &VerifyingKey) -> Self {
+        let uncompressed = key.to_encoded_point(false);
+        let hash = Keccak256::digest(&uncompressed.as_bytes()[1..]);
+        let mut out = [0u8; 20];
+        out.copy_from_slice(&hash[12..]);
+        Address(out)
+    }
+}
+
+/// An EIP-712 typed quote: pay `max_amount_required` of `token` to `pay_to`
+/// for `resource`, valid until `valid_until` (unix seconds), scoped to
+/// `client_id` and made unique by `nonce`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Quote {
+    pub resource: String,
+    pub pay_to: Address,
+    pub token: Address,
+    pub max_amount_required: u128,
+    pub valid_until: u64,
+    pub nonce: String,
+    pub client_id: String,
+    /// The USD value `max_amount_required` was quoted from, kept around so
+    /// a client that prefers a different accepted token can be re-quoted
+    /// against the same target value. Part of the signed digest: if it
+    /// weren't, a client could present a `Quote` with a tampered `want_usd`
+    /// to `resolve_payment_requirements`'s re-quoting fan-out and have other
+    /// accepted tokens priced from a value the server never signed off on.
+    pub want_usd: String,
+}
+
+const EIP712_DOMAIN_NAME: &str = "x402-axum-quote-example";
+const EIP712_DOMAIN_VERSION: &str = "1";
+const QUOTE_TYPE_HASH: &str =
+    "Quote(string resource,address payTo,address token,uint256 maxAmountRequired,uint256 validUntil,string nonce,string clientId,string wantUsd)";
+
+/// Left-pads a 20-byte address into a 32-byte EIP-712 word.
+fn encode_address(address: &Address) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[12..].copy_from_slice(&address.0);
+    word
+}
+
+/// Big-endian-encodes a `uint256` value into a 32-byte EIP-712 word.
+fn encode_uint256(value: u128) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[16..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+impl Quote {
+    /// The EIP-712 digest signed by the server and recovered by the
+    /// resolver; see [EIP-712](https://eips.ethereum.org/EIPS/eip-712).
+    ///
+    /// Every encoded struct member is a 32-byte word, per the spec:
+    /// addresses are left-padded with 12 zero bytes and integers are
+    /// big-endian `uint256`s, so the digest matches what a standard
+    /// EIP-712 implementation (ethers.js, viem, ...) would produce for
+    /// the same typed data.
+    ///
+    /// `want_usd` is included: it drives how other accepted tokens get
+    /// re-priced, so leaving it out of the digest would let a client swap
+    /// in a different `want_usd` after the signature was produced.
+    pub fn digest(&self) -> [u8; 32] {
+        let domain_separator = Keccak256::new()
+            .chain_update(Keccak256::digest("EIP712Domain(string name,string version)"))
+            .chain_update(Keccak256::digest(EIP712_DOMAIN_NAME))
+            .chain_update(Keccak256::digest(EIP712_DOMAIN_VERSION))
+            .finalize();
+
+        let struct_hash = Keccak256::new()
+            .chain_update(Keccak256::digest(QUOTE_TYPE_HASH))
+            .chain_update(Keccak256::digest(&self.resource))
+            .chain_update(encode_address(&self.pay_to))
+            .chain_update(encode_address(&self.token))
+            .chain_update(encode_uint256(self.max_amount_required))
+            .chain_update(encode_uint256(self.valid_until as u128))
+            .chain_update(Keccak256::digest(&self.nonce))
+            .chain_update(Keccak256::digest(&self.client_id))
+            .chain_update(Keccak256::digest(&self.want_usd))
+            .finalize();
+
+        let mut hasher = Keccak256::new();
+        hasher.update([0x19, 0x01]);
+        hasher.update(domain_separator);
+        hasher.update(struct_hash);
+        hasher.finalize().into()
+    }
+}
+
+/// Signs [`Quote`]s on behalf of the resource server.
+pub struct QuoteSigner {
+    signing_key: SigningKey,
+}
+
+impl QuoteSigner {
+    pub fn new(signing_key: SigningKey) -> Self {
+        Self { signing_key }
+    }
+
+    /// Signs `quote`'s EIP-712 digest, returning a 65-byte `r || s || v`
+    /// signature.
+    pub fn sign(&self, quote: &Quote) -> [u8; 65] {
+        let (sig, recovery_id): (EcdsaSignature, RecoveryId) = self
+            .signing_key
+            .sign_prehash_recoverable(&quote.digest())
+            .expect("quote digest is a valid message hash");
+        let mut out = [0u8; 65];
+        out[..64].copy_from_slice(&sig.to_bytes());
+        out[64] = recovery_id.to_byte();
+        out
+    }
+}
+
+#[derive(Debug)]
+pub enum VerifyError {
+    BadSignature,
+    WrongSigner,
+    Expired,
+    ResourceMismatch,
+    ClientMismatch,
+}
+
+/// Recovers the signer of a [`Quote`] and checks it against the configured
+/// server address, plus the quote's validity window and scoping.
+pub struct SignedQuoteVerifier {
+    server_address: Address,
+}
+
+impl SignedQuoteVerifier {
+    pub fn new(server_address: Address) -> Self {
+        Self { server_address }
+    }
+
+    pub fn verify(
+        &self,
+        quote: &Quote,
+        signature: &[u8; 65],
+        resource: &str,
+        client_id: &str,
+        now: u64,
+    ) -> Result<(), VerifyError> {
+        let sig =
+            EcdsaSignature::from_slice(&signature[..64]).map_err(|_| VerifyError::BadSignature)?;
+        let recovery_id =
+            RecoveryId::from_byte(signature[64]).ok_or(VerifyError::BadSignature)?;
+        let recovered = VerifyingKey::recover_from_prehash(&quote.digest(), &sig, recovery_id)
+            .map_err(|_| VerifyError::BadSignature)?;
+        if Address::from_verifying_key(&recovered) != self.server_address {
+            return Err(VerifyError::WrongSigner);
+        }
+        if quote.valid_until <= now {
+            return Err(VerifyError::Expired);
+        }
+        if quote.resource != resource {
+            return Err(VerifyError::ResourceMismatch);
+        }
+        if quote.client_id != client_id {
+            return Err(VerifyError::ClientMismatch);
+        }
+        Ok(())
+    }
+}
+
+/// Replay protection for signed quotes, keyed on [`Quote::nonce`].
+///
+/// The default [`InMemoryNonceStore`] is process-local; a deployment
+/// running multiple resource-server replicas should back this with
+/// something shared (e.g. Redis) instead.
+#[async_trait::async_trait]
+pub trait NonceStore: Send + Sync {
+    /// Returns `true` if `nonce` had not been seen before (and records it
+    /// as seen), `false` if it's a replay.
+    async fn claim(&self, nonce: &str) -> bool;
+}
+
+#[derive(Default)]
+pub struct InMemoryNonceStore {
+    seen: Mutex<HashSet<String>>,
+}
+
+#[async_trait::async_trait]
+impl NonceStore for InMemoryNonceStore {
+    async fn claim(&self, nonce: &str) -> bool {
+        self.seen.lock().await.insert(nonce.to_string())
+    }
+}
+
+pub type SharedNonceStore = Arc<dyn NonceStore>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_quote() -> Quote {
+        Quote {
+            resource: "https://example.com/resource".to_string(),
+            pay_to: Address([0x11; 20]),
+            token: Address([0x22; 20]),
+            max_amount_required: 10_000,
+            valid_until: 9_999_999_999,
+            nonce: "nonce-1".to_string(),
+            client_id: "client-1".to_string(),
+            want_usd: "0.01".to_string(),
+        }
+    }
+
+    #[test]
+    fn sign_and_verify_round_trip() {
+        let signing_key = SigningKey::from_slice(&[0x42; 32]).unwrap();
+        let server_address = Address::from_verifying_key(signing_key.verifying_key());
+        let signer = QuoteSigner::new(signing_key);
+        let verifier = SignedQuoteVerifier::new(server_address);
+
+        let quote = sample_quote();
+        let signature = signer.sign(&quote);
+
+        verifier
+            .verify(&quote, &signature, &quote.resource, &quote.client_id, 0)
+            .expect("a freshly signed quote should verify");
+    }
+
+    #[test]
+    fn verify_rejects_wrong_signer() {
+        let signing_key = SigningKey::from_slice(&[0x42; 32]).unwrap();
+        let other_signer_address =
+            Address::from_verifying_key(SigningKey::from_slice(&[0x43; 32]).unwrap().verifying_key());
+        let signer = QuoteSigner::new(signing_key);
+        let verifier = SignedQuoteVerifier::new(other_signer_address);
+
+        let quote = sample_quote();
+        let signature = signer.sign(&quote);
+
+        assert!(matches!(
+            verifier.verify(&quote, &signature, &quote.resource, &quote.client_id, 0),
+            Err(VerifyError::WrongSigner)
+        ));
+    }
+
+    #[test]
+    fn verify_rejects_expired_quote() {
+        let signing_key = SigningKey::from_slice(&[0x42; 32]).unwrap();
+        let server_address = Address::from_verifying_key(signing_key.verifying_key());
+        let signer = QuoteSigner::new(signing_key);
+        let verifier = SignedQuoteVerifier::new(server_address);
+
+        let quote = sample_quote();
+        let signature = signer.sign(&quote);
+
+        assert!(matches!(
+            verifier.verify(
+                &quote,
+                &signature,
+                &quote.resource,
+                &quote.client_id,
+                quote.valid_until + 1,
+            ),
+            Err(VerifyError::Expired)
+        ));
+    }
+
+    #[test]
+    fn address_serializes_as_hex_string_not_byte_array() {
+        let address = Address([0xAB; 20]);
+        let json = serde_json::to_string(&address).unwrap();
+        assert_eq!(json, "\"0xabababababababababababababababababababab\"");
+
+        let round_tripped: Address = serde_json::from_str(&json).unwrap();
+        assert_eq!(round_tripped, address);
+    }
+
+    #[tokio::test]
+    async fn nonce_store_rejects_replay() {
+        let store = InMemoryNonceStore::default();
+        assert!(store.claim("nonce-1").await);
+        assert!(!store.claim("nonce-1").await);
+    }
+
+    #[test]
+    fn verify_rejects_quote_with_tampered_want_usd() {
+        // `want_usd` must be covered by the digest: otherwise a client could
+        // present a genuinely-signed quote with `want_usd` swapped out and
+        // have it verify, forging the value other accepted tokens get
+        // re-quoted against.
+        let signing_key = SigningKey::from_slice(&[0x42; 32]).unwrap();
+        let server_address = Address::from_verifying_key(signing_key.verifying_key());
+        let signer = QuoteSigner::new(signing_key);
+        let verifier = SignedQuoteVerifier::new(server_address);
+
+        let quote = sample_quote();
+        let signature = signer.sign(&quote);
+
+        let mut tampered = quote.clone();
+        tampered.want_usd = "1000000.00".to_string();
+
+        // Recovery runs against the tampered digest, so it resolves to some
+        // public key other than the server's — the signature doesn't
+        // cryptographically fail, but the recovered signer no longer
+        // matches.
+        assert!(matches!(
+            verifier.verify(&tampered, &signature, &tampered.resource, &tampered.client_id, 0),
+            Err(VerifyError::WrongSigner)
+        ));
+    }
+}